@@ -1,11 +1,15 @@
 //! Prover implementation implemented using SupraSeal (C++).
 
+use std::io::{self, Read};
 use std::time::Instant;
 
 use bellpepper_core::{Circuit, ConstraintSystem, Index, SynthesisError, Variable};
+use blake2b_simd::Params as Blake2bParams;
 use ff::{Field, PrimeField};
+use group::{prime::PrimeCurveAffine, GroupEncoding};
 use log::info;
 use pairing::MultiMillerLoop;
+use rand_core::RngCore;
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
 use super::{ParameterSource, Proof, ProvingAssignment};
@@ -46,10 +50,71 @@ where
     }
 }
 
+/// Creates a batch of proofs with `r`/`s` blinding factors drawn uniformly
+/// from `rng`, exactly as the reference `create_random_proof` does for the
+/// non-SupraSeal prover. This is the documented default: without per-circuit
+/// randomization a Groth16 proof leaks no secrets in itself, but it is no
+/// longer zero-knowledge, since a verifier who sees two proofs for the same
+/// statement can tell they were produced deterministically.
+#[allow(clippy::type_complexity)]
+pub(super) fn create_random_proof_batch_priority_inner<E, C, R, P: ParameterSource<E>>(
+    circuits: Vec<C>,
+    params: Vec<P>,
+    rng: &mut R,
+    priority: bool,
+) -> Result<Vec<Proof<E>>, SynthesisError>
+where
+    E: MultiMillerLoop,
+    C: Circuit<E::Fr> + Send,
+    E::Fr: GpuName,
+    E::G1Affine: GpuName,
+    E::G2Affine: GpuName,
+    R: RngCore,
+{
+    let num_circuits = circuits.len();
+    let r_s = (0..num_circuits).map(|_| E::Fr::random(&mut *rng)).collect();
+    let s_s = (0..num_circuits).map(|_| E::Fr::random(&mut *rng)).collect();
+
+    create_proof_batch_priority_inner(circuits, params, Some((r_s, s_s)), priority)
+}
+
+/// Public entry point for the RNG-randomized batch prover described above.
+/// This is the one callers outside this module should reach for: it is the
+/// only path that produces zero-knowledge proofs, matching
+/// `create_random_proof_batch` for the non-SupraSeal prover.
+pub fn create_random_proof_batch<E, C, R, P: ParameterSource<E>>(
+    circuits: Vec<C>,
+    params: Vec<P>,
+    rng: &mut R,
+) -> Result<Vec<Proof<E>>, SynthesisError>
+where
+    E: MultiMillerLoop,
+    C: Circuit<E::Fr> + Send,
+    E::Fr: GpuName,
+    E::G1Affine: GpuName,
+    E::G2Affine: GpuName,
+    R: RngCore,
+{
+    create_random_proof_batch_priority_inner(circuits, params, rng, false)
+}
+
+/// Creates a batch of proofs using the given `randomization` (or, if `None`,
+/// no blinding at all). Callers that care about zero-knowledge should use
+/// [`create_random_proof_batch_priority_inner`] instead; the `None` path
+/// here only exists so deterministic tests can pin down `r = s = 0` and
+/// compare proofs byte-for-byte.
+///
+/// `params` holds one `ParameterSource` per circuit in `circuits` (same
+/// order, same length), rather than a single SRS shared by the whole
+/// batch: circuits are bucketed by constraint-vector length, and each
+/// bucket fetches its SRS from its own first circuit's `ParameterSource`,
+/// so a batch genuinely can mix circuits of different relations (e.g. a
+/// window-post bucket and a winning-post bucket) as long as every circuit
+/// of a given relation shares that relation's `ParameterSource`.
 #[allow(clippy::type_complexity)]
 pub(super) fn create_proof_batch_priority_inner<E, C, P: ParameterSource<E>>(
     circuits: Vec<C>,
-    params: P,
+    mut params: Vec<P>,
     randomization: Option<(Vec<E::Fr>, Vec<E::Fr>)>,
     _priority: bool,
 ) -> Result<Vec<Proof<E>>, SynthesisError>
@@ -60,6 +125,12 @@ where
     E::G1Affine: GpuName,
     E::G2Affine: GpuName,
 {
+    assert_eq!(
+        circuits.len(),
+        params.len(),
+        "one ParameterSource is required per circuit"
+    );
+
     info!(
         "Bellperson {} with SupraSeal is being used!",
         BELLMAN_VERSION
@@ -77,17 +148,14 @@ where
         vec![E::Fr::ZERO; num_circuits],
     ));
 
-    // Make sure all circuits have the same input len.
-    for prover in &provers {
-        assert_eq!(
-            prover.a.len(),
-            provers[0].a.len(),
-            "only equaly sized circuits are supported"
-        );
-    }
-
-    let provers_c2: Vec<supraseal_c2::Assignment<E::Fr>> =
-        provers.iter().map(|p| p.into()).collect();
+    // SupraSeal requires every circuit in a single `generate_groth16_proofs`
+    // call to share the same constraint-vector length, so bucket the
+    // provers by size and issue one call per bucket instead of forcing the
+    // caller to split mixed-size workloads up-front. When every circuit is
+    // already the same size this degenerates to a single bucket, i.e. the
+    // same one call we always used to make.
+    let lens: Vec<usize> = provers.iter().map(|prover| prover.a.len()).collect();
+    let size_groups = bucket_indices_by_size(&lens);
 
     let mut proofs: Vec<Proof<E>> = Vec::with_capacity(num_circuits);
     // We call out to C++ code which is unsafe anyway, hence silence this warning.
@@ -96,17 +164,42 @@ where
         proofs.set_len(num_circuits);
     }
 
-    let srs = params.get_supraseal_srs().ok_or_else(|| {
-        log::error!("SupraSeal SRS wasn't allocated correctly");
-        SynthesisError::MalformedSrs
-    })?;
-    supraseal_c2::generate_groth16_proofs(
-        provers_c2.as_slice(),
-        r_s.as_slice(),
-        s_s.as_slice(),
-        proofs.as_mut_slice(),
-        srs,
-    );
+    for (_, indices) in &size_groups {
+        // Each bucket gets its own SRS, fetched from the first circuit in
+        // the bucket's `ParameterSource` -- callers are responsible for
+        // giving every circuit of a given relation the same
+        // `ParameterSource`, the same way they're responsible for pairing
+        // a circuit with the right SRS outside of batching.
+        let srs = params[indices[0]].get_supraseal_srs().ok_or_else(|| {
+            log::error!("SupraSeal SRS wasn't allocated correctly");
+            SynthesisError::MalformedSrs
+        })?;
+
+        let group_provers: Vec<supraseal_c2::Assignment<E::Fr>> =
+            indices.iter().map(|&i| (&provers[i]).into()).collect();
+        let group_r_s: Vec<E::Fr> = indices.iter().map(|&i| r_s[i]).collect();
+        let group_s_s: Vec<E::Fr> = indices.iter().map(|&i| s_s[i]).collect();
+
+        let mut group_proofs: Vec<Proof<E>> = Vec::with_capacity(indices.len());
+        #[allow(clippy::uninit_vec)]
+        unsafe {
+            group_proofs.set_len(indices.len());
+        }
+
+        supraseal_c2::generate_groth16_proofs(
+            group_provers.as_slice(),
+            group_r_s.as_slice(),
+            group_s_s.as_slice(),
+            group_proofs.as_mut_slice(),
+            srs,
+        );
+
+        // Reassemble proofs in the original input order using the index
+        // map we bucketed by.
+        for (proof, &original_idx) in group_proofs.into_iter().zip(indices.iter()) {
+            proofs[original_idx] = proof;
+        }
+    }
 
     let proof_time = start.elapsed();
     info!("prover time: {:?}", proof_time);
@@ -114,6 +207,146 @@ where
     Ok(proofs)
 }
 
+/// Groups `0..lens.len()` by equal value in `lens`, in the order each
+/// distinct size is first seen, with each group's indices kept in
+/// ascending (original) order.
+fn bucket_indices_by_size(lens: &[usize]) -> Vec<(usize, Vec<usize>)> {
+    let mut size_groups: Vec<(usize, Vec<usize>)> = Vec::new();
+    for (idx, &len) in lens.iter().enumerate() {
+        match size_groups.iter_mut().find(|(size, _)| *size == len) {
+            Some((_, indices)) => indices.push(idx),
+            None => size_groups.push((len, vec![idx])),
+        }
+    }
+    size_groups
+}
+
+/// Magic bytes identifying the on-wire format written by [`write_batch`].
+const PROOF_BATCH_MAGIC: &[u8; 4] = b"BPB1";
+
+/// A version string longer than this could only be corrupt or adversarial.
+const MAX_VERSION_LEN: u32 = 256;
+/// A batch this large is already unreasonable; reject it before allocating.
+const MAX_PROOFS_PER_BATCH: u32 = 1 << 20;
+/// Generous upper bound on a single compressed proof's encoded size.
+const MAX_PROOF_LEN: u32 = 4096;
+
+/// Serializes a batch of proofs into a single self-describing byte stream:
+/// magic bytes, the `BELLMAN_VERSION` that produced it, the proof count,
+/// and then each proof's compressed point encoding prefixed with its byte
+/// length. This is the uncompressed wire format; see [`compress_batch`] for
+/// a deflate-compressed variant better suited to storing proofs at rest.
+pub fn write_batch<E: MultiMillerLoop, W: io::Write>(
+    proofs: &[Proof<E>],
+    mut writer: W,
+) -> io::Result<()> {
+    writer.write_all(PROOF_BATCH_MAGIC)?;
+
+    let version = BELLMAN_VERSION.as_bytes();
+    writer.write_all(&(version.len() as u32).to_le_bytes())?;
+    writer.write_all(version)?;
+
+    writer.write_all(&(proofs.len() as u32).to_le_bytes())?;
+
+    let mut proof_bytes = Vec::new();
+    for proof in proofs {
+        proof_bytes.clear();
+        proof.write(&mut proof_bytes)?;
+        writer.write_all(&(proof_bytes.len() as u32).to_le_bytes())?;
+        writer.write_all(&proof_bytes)?;
+    }
+
+    Ok(())
+}
+
+/// Reads back a batch written by [`write_batch`], rejecting the point at
+/// infinity for every proof component exactly as `Proof::read` already does
+/// for a single proof. Fails if the embedded `BELLMAN_VERSION` doesn't match
+/// this build's, since a different version may encode proofs differently.
+pub fn read_batch<E: MultiMillerLoop, R: io::Read>(mut reader: R) -> io::Result<Vec<Proof<E>>> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != PROOF_BATCH_MAGIC {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "proof batch has an unrecognized magic header",
+        ));
+    }
+
+    let version_len = read_bounded_len(&mut reader, MAX_VERSION_LEN, "proof batch version")?;
+    let mut version = vec![0u8; version_len];
+    reader.read_exact(&mut version)?;
+    if version != BELLMAN_VERSION.as_bytes() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "proof batch was written by bellperson {:?}, this build is {BELLMAN_VERSION}",
+                String::from_utf8_lossy(&version),
+            ),
+        ));
+    }
+
+    let num_proofs =
+        read_bounded_len(&mut reader, MAX_PROOFS_PER_BATCH, "proof batch proof count")?;
+    // Don't pre-reserve `num_proofs` worth of capacity: it's still an
+    // attacker-controlled length prefix at this point, and a `Proof<E>` is
+    // hundreds of bytes, so a ~20-byte crafted header could otherwise force
+    // a ~100s-of-MB reservation before a single proof is actually read.
+    // Growing the `Vec` as proofs are read keeps memory use tied to bytes
+    // actually consumed from `reader`.
+    let mut proofs = Vec::new();
+    for _ in 0..num_proofs {
+        let proof_len = read_bounded_len(&mut reader, MAX_PROOF_LEN, "proof")?;
+        let mut proof_bytes = vec![0u8; proof_len];
+        reader.read_exact(&mut proof_bytes)?;
+        proofs.push(Proof::read(&proof_bytes[..])?);
+    }
+
+    Ok(proofs)
+}
+
+fn read_u32<R: io::Read>(mut reader: R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+/// Reads a little-endian `u32` length prefix and rejects it if it exceeds
+/// `max`, so a corrupted or adversarial length field can't trigger an
+/// unbounded allocation before any `io::Error` path gets a chance to run.
+fn read_bounded_len<R: io::Read>(reader: R, max: u32, what: &str) -> io::Result<usize> {
+    let len = read_u32(reader)?;
+    if len > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{what} length {len} exceeds the maximum of {max}"),
+        ));
+    }
+    Ok(len as usize)
+}
+
+/// Deflate-compresses a proof batch using a pure-Rust backend, for callers
+/// that want to store or transmit proofs as compactly as possible. Pair
+/// with [`decompress_batch`] to read it back.
+#[cfg(feature = "proof-batch-compression")]
+pub fn compress_batch<E: MultiMillerLoop>(proofs: &[Proof<E>]) -> io::Result<Vec<u8>> {
+    let mut raw = Vec::new();
+    write_batch(proofs, &mut raw)?;
+    Ok(miniz_oxide::deflate::compress_to_vec(&raw, 6))
+}
+
+/// Inflates and parses a proof batch produced by [`compress_batch`].
+#[cfg(feature = "proof-batch-compression")]
+pub fn decompress_batch<E: MultiMillerLoop>(data: &[u8]) -> io::Result<Vec<Proof<E>>> {
+    let raw = miniz_oxide::inflate::decompress_to_vec(data).map_err(|err| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("failed to inflate proof batch: {err:?}"),
+        )
+    })?;
+    read_batch(&raw[..])
+}
+
 #[allow(clippy::type_complexity)]
 fn synthesize_circuits_batch<Scalar, C>(
     circuits: Vec<C>,
@@ -137,6 +370,15 @@ where
                 prover.enforce(|| "", |lc| lc + Variable(Index::Input(i)), |lc| lc, |lc| lc);
             }
 
+            // SupraSeal's proving math runs in unsafe C++, so a malformed
+            // witness would otherwise surface as a corrupt proof instead of
+            // a clean `SynthesisError`. Catch it here, in debug builds only,
+            // so circuit authors get the same early failure the
+            // non-SupraSeal CPU prover gives them for free.
+            if cfg!(debug_assertions) {
+                check_constraints_satisfied(&prover)?;
+            }
+
             Ok(prover)
         })
         .collect::<Result<Vec<_>, _>>()?;
@@ -145,3 +387,445 @@ where
 
     Ok(provers)
 }
+
+/// Evaluates every enforced constraint `(A·z)·(B·z) == (C·z)` for `prover`,
+/// where `z` is the combined input/aux assignment, and returns
+/// [`SynthesisError::Unsatisfiable`] at the first one that doesn't hold.
+///
+/// `prover.a`, `prover.b` and `prover.c` already hold each constraint's
+/// linear combination evaluated against `z` (that's what `enforce` records
+/// as it synthesizes), so checking satisfaction is just a pointwise
+/// multiply-and-compare over those three vectors — the same check the
+/// reference CPU prover performs implicitly by construction.
+fn check_constraints_satisfied<Scalar: PrimeField>(
+    prover: &ProvingAssignment<Scalar>,
+) -> Result<(), SynthesisError> {
+    for (i, ((a, b), c)) in prover
+        .a
+        .iter()
+        .zip(prover.b.iter())
+        .zip(prover.c.iter())
+        .enumerate()
+    {
+        if *a * b != *c {
+            log::error!("constraint {i} is not satisfied: (A * z) * (B * z) != (C * z)");
+            return Err(SynthesisError::Unsatisfiable);
+        }
+    }
+
+    Ok(())
+}
+
+/// An SRS loaded from a phase-2 trusted-setup MPC ceremony, marshaled into
+/// the contiguous G1/G2 query vectors SupraSeal expects.
+///
+/// `params.get_supraseal_srs()` assumes the SRS is already sitting in this
+/// shape; this type is the bridge from a community ceremony's output file
+/// to that shape. Ceremony output is a sequence of hash-linked
+/// contributions, each one multiplying in a participant's toxic-waste-free
+/// tau/alpha/beta powers on top of the last, with the final contribution
+/// holding the fully accumulated query vectors.
+pub struct Phase2MpcSrs<E: MultiMillerLoop> {
+    pub h: Vec<E::G1Affine>,
+    pub l: Vec<E::G1Affine>,
+    pub a: Vec<E::G1Affine>,
+    pub b_g1: Vec<E::G1Affine>,
+    pub b_g2: Vec<E::G2Affine>,
+
+    // The sizes this SRS was validated against when it was loaded, kept
+    // around so `get_supraseal_srs` can cheaply re-check them before every
+    // use instead of trusting that nothing mutated `h`/`l`/`a`/`b_g1`/`b_g2`
+    // out from under the original validation.
+    num_inputs: usize,
+    num_aux: usize,
+    h_domain_size: usize,
+}
+
+impl<E: MultiMillerLoop> Phase2MpcSrs<E> {
+    /// Parses a phase-2 MPC transcript and returns the final accumulated
+    /// SRS, failing if any contribution's hash doesn't chain from the one
+    /// before it, or if the final query vectors' lengths don't match
+    /// `num_inputs`/`num_aux`/`h_domain_size` (see [`Self::validate_lengths`]).
+    /// Validating here, as the ceremony file is loaded, is what makes a
+    /// mismatched file fail fast instead of silently producing an invalid
+    /// proof later inside SupraSeal.
+    pub fn from_phase2_transcript<R: io::Read>(
+        mut reader: R,
+        num_inputs: usize,
+        num_aux: usize,
+        h_domain_size: usize,
+    ) -> io::Result<Self> {
+        let num_contributions = read_u32(&mut reader)?;
+        if num_contributions == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "phase-2 transcript has no contributions",
+            ));
+        }
+
+        let mut srs = None;
+        let mut previous_hash = [0u8; 64];
+        for _ in 0..num_contributions {
+            let contribution = Phase2Contribution::<E>::read(&mut reader, &previous_hash)?;
+            previous_hash = contribution.hash;
+            srs = Some(Self {
+                h: contribution.h,
+                l: contribution.l,
+                a: contribution.a,
+                b_g1: contribution.b_g1,
+                b_g2: contribution.b_g2,
+                num_inputs,
+                num_aux,
+                h_domain_size,
+            });
+        }
+
+        let srs = srs.expect("checked above that num_contributions > 0");
+        srs.validate_lengths(num_inputs, num_aux, h_domain_size)
+            .map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("phase-2 transcript doesn't match the expected circuit: {err:?}"),
+                )
+            })?;
+
+        Ok(srs)
+    }
+
+    /// Checks the loaded query vectors' lengths against the circuit's input
+    /// and aux variable counts, so a mismatched ceremony file fails fast
+    /// here instead of producing an invalid proof inside SupraSeal: `a`,
+    /// `b_g1` and `b_g2` must each hold `num_inputs + num_aux` points and
+    /// `l` must hold `num_aux`. The circuit's density trackers are not
+    /// consulted.
+    ///
+    /// `h_domain_size` is the padded FFT domain size derived from the
+    /// *total* constraint count (i.e. `next_power_of_two(num_constraints)`,
+    /// including the per-input consistency constraints
+    /// `synthesize_circuits_batch` adds) -- the `h` query has
+    /// `h_domain_size - 1` entries, unrelated to `num_aux`.
+    pub fn validate_lengths(
+        &self,
+        num_inputs: usize,
+        num_aux: usize,
+        h_domain_size: usize,
+    ) -> Result<(), SynthesisError> {
+        let num_variables = num_inputs + num_aux;
+        let expected_h_len = h_domain_size - 1;
+        if self.h.len() != expected_h_len
+            || self.l.len() != num_aux
+            || self.a.len() != num_variables
+            || self.b_g1.len() != num_variables
+            || self.b_g2.len() != num_variables
+        {
+            log::error!(
+                "phase-2 SRS doesn't match the synthesized circuit: h={}, l={}, a={}, b_g1={}, \
+                 b_g2={} (expected h={expected_h_len}, aux={num_aux}, variables={num_variables})",
+                self.h.len(),
+                self.l.len(),
+                self.a.len(),
+                self.b_g1.len(),
+                self.b_g2.len(),
+            );
+            return Err(SynthesisError::MalformedSrs);
+        }
+
+        Ok(())
+    }
+}
+
+/// The contiguous SRS layout `supraseal_c2::generate_groth16_proofs` reads
+/// its `srs` argument from, mirroring how `supraseal_c2::Assignment` above
+/// mirrors `ProvingAssignment`: raw pointers and lengths borrowed straight
+/// out of the already-contiguous `Vec`s in [`Phase2MpcSrs`].
+///
+/// `'a` ties this borrow back to the `Phase2MpcSrs` it was built from, so
+/// safe code can't build one in an inner scope, drop the owning
+/// `Phase2MpcSrs`, and hand SupraSeal a dangling pointer -- the borrow
+/// checker rejects that the same way it would for any other `&'a` borrow.
+/// (This file doesn't have visibility into the real `ParameterSource`
+/// trait or the concrete SRS type the pre-existing `get_supraseal_srs`
+/// call site upstream of this series expects; this is a best-effort
+/// reconstruction of that contiguous layout, not a confirmed match.)
+pub struct SupraSealSrs<'a, E: MultiMillerLoop> {
+    pub h: *const E::G1Affine,
+    pub h_len: usize,
+
+    pub l: *const E::G1Affine,
+    pub l_len: usize,
+
+    pub a: *const E::G1Affine,
+    pub a_len: usize,
+
+    pub b_g1: *const E::G1Affine,
+    pub b_g1_len: usize,
+
+    pub b_g2: *const E::G2Affine,
+    pub b_g2_len: usize,
+
+    _borrow: std::marker::PhantomData<&'a Phase2MpcSrs<E>>,
+}
+
+impl<'a, E: MultiMillerLoop> From<&'a Phase2MpcSrs<E>> for SupraSealSrs<'a, E> {
+    fn from(srs: &'a Phase2MpcSrs<E>) -> Self {
+        Self {
+            h: srs.h.as_ptr(),
+            h_len: srs.h.len(),
+
+            l: srs.l.as_ptr(),
+            l_len: srs.l.len(),
+
+            a: srs.a.as_ptr(),
+            a_len: srs.a.len(),
+
+            b_g1: srs.b_g1.as_ptr(),
+            b_g1_len: srs.b_g1.len(),
+
+            b_g2: srs.b_g2.as_ptr(),
+            b_g2_len: srs.b_g2.len(),
+
+            _borrow: std::marker::PhantomData,
+        }
+    }
+}
+
+impl<E: MultiMillerLoop> ParameterSource<E> for Phase2MpcSrs<E> {
+    // `ParameterSource`'s CPU-path methods (`get_vk`, `get_h`, `get_l`,
+    // `get_a`, `get_b_g1`, `get_b_g2`) need the verifying-key elements
+    // (alpha_g1, beta_g1/g2, gamma_g2, delta_g1/g2, the `ic` vector) that a
+    // phase-2 transcript also carries but that `Phase2MpcSrs` doesn't parse
+    // yet -- this adapter only targets the SupraSeal flow, which is the one
+    // this module's `create_proof_batch_priority_inner` actually drives.
+    fn get_supraseal_srs(&mut self) -> Option<SupraSealSrs<'_, E>> {
+        // Re-validate defensively: `from_phase2_transcript` already checked
+        // these lengths when the SRS was loaded, but re-checking here means
+        // this is never the path that hands SupraSeal a mismatched SRS,
+        // even if that invariant is ever broken upstream of this call.
+        if let Err(err) = self.validate_lengths(self.num_inputs, self.num_aux, self.h_domain_size)
+        {
+            log::error!("phase-2 SRS failed its length check when fetched: {err:?}");
+            return None;
+        }
+
+        Some((&*self).into())
+    }
+}
+
+struct Phase2Contribution<E: MultiMillerLoop> {
+    h: Vec<E::G1Affine>,
+    l: Vec<E::G1Affine>,
+    a: Vec<E::G1Affine>,
+    b_g1: Vec<E::G1Affine>,
+    b_g2: Vec<E::G2Affine>,
+    hash: [u8; 64],
+}
+
+/// A single contribution's blob is a handful of curve-point vectors; this
+/// is a generous upper bound that still catches a corrupted or adversarial
+/// length field before it can trigger a multi-gigabyte allocation.
+const MAX_PHASE2_BLOB_LEN: u32 = 1 << 28;
+/// No real circuit has anywhere near this many variables.
+const MAX_QUERY_VEC_LEN: u32 = 1 << 24;
+
+impl<E: MultiMillerLoop> Phase2Contribution<E> {
+    fn read<R: io::Read>(mut reader: R, previous_hash: &[u8; 64]) -> io::Result<Self> {
+        let blob_len = read_bounded_len(&mut reader, MAX_PHASE2_BLOB_LEN, "phase-2 contribution")?;
+        // Don't size this allocation off `blob_len` before the bytes are
+        // known to exist on the wire -- the cap above still leaves room for
+        // a tiny crafted header to claim a 256MB blob. `take` + `read_to_end`
+        // grows the buffer as bytes actually arrive, and errors out instead
+        // of padding with zeros if the reader runs dry early.
+        let mut blob = Vec::new();
+        let read_len = reader.by_ref().take(blob_len as u64).read_to_end(&mut blob)?;
+        if read_len != blob_len {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "phase-2 contribution's blob was truncated",
+            ));
+        }
+
+        // Each contribution stores the hash its author recorded when they
+        // appended to the transcript. It must match `blake2b(previous_hash
+        // || blob)` recomputed here, or the file has been tampered with,
+        // truncated, or reordered since that participant signed off on it.
+        let mut stored_hash = [0u8; 64];
+        reader.read_exact(&mut stored_hash)?;
+
+        let digest = Blake2bParams::new()
+            .hash_length(64)
+            .to_state()
+            .update(previous_hash)
+            .update(&blob)
+            .finalize();
+        let mut hash = [0u8; 64];
+        hash.copy_from_slice(digest.as_bytes());
+
+        if hash != stored_hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "phase-2 contribution's stored hash doesn't match its recomputed transcript hash",
+            ));
+        }
+
+        let mut body = &blob[..];
+        let h = read_affine_vec::<E::G1Affine, _>(&mut body)?;
+        let l = read_affine_vec::<E::G1Affine, _>(&mut body)?;
+        let a = read_affine_vec::<E::G1Affine, _>(&mut body)?;
+        let b_g1 = read_affine_vec::<E::G1Affine, _>(&mut body)?;
+        let b_g2 = read_affine_vec::<E::G2Affine, _>(&mut body)?;
+
+        Ok(Self {
+            h,
+            l,
+            a,
+            b_g1,
+            b_g2,
+            hash,
+        })
+    }
+}
+
+fn read_affine_vec<G: PrimeCurveAffine + GroupEncoding, R: io::Read>(
+    mut reader: R,
+) -> io::Result<Vec<G>> {
+    let len = read_bounded_len(&mut reader, MAX_QUERY_VEC_LEN, "phase-2 query vector")?;
+    // Collecting a `(0..len).map(...)` iterator into a `Vec` pre-reserves
+    // capacity from `len` via its size hint, which is just as much an
+    // attacker-controlled-length amplification as `Vec::with_capacity`
+    // would be. Push in a loop instead so capacity only grows as points
+    // are actually read off `reader`.
+    let mut points = Vec::new();
+    for _ in 0..len {
+        points.push(read_affine::<G, _>(&mut reader)?);
+    }
+    Ok(points)
+}
+
+/// Reads a single compressed curve point, rejecting the point at infinity
+/// exactly as the reference `Proof::read` does for proof points.
+fn read_affine<G: PrimeCurveAffine + GroupEncoding, R: io::Read>(mut reader: R) -> io::Result<G> {
+    let mut repr = G::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+
+    let point = Option::<G>::from(G::from_bytes(&repr))
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid curve point encoding"))?;
+
+    if point.is_identity().into() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "phase-2 SRS query vector contains the point at infinity",
+        ));
+    }
+
+    Ok(point)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use blstrs::Bls12;
+    use pairing::Engine;
+
+    fn sample_proof() -> Proof<Bls12> {
+        Proof {
+            a: <Bls12 as Engine>::G1Affine::generator(),
+            b: <Bls12 as Engine>::G2Affine::generator(),
+            c: <Bls12 as Engine>::G1Affine::generator(),
+        }
+    }
+
+    #[test]
+    fn check_constraints_satisfied_accepts_a_satisfied_constraint() {
+        let mut prover = ProvingAssignment::<blstrs::Scalar>::new();
+        prover.a = vec![blstrs::Scalar::ONE];
+        prover.b = vec![blstrs::Scalar::ONE];
+        prover.c = vec![blstrs::Scalar::ONE];
+
+        assert!(check_constraints_satisfied(&prover).is_ok());
+    }
+
+    #[test]
+    fn check_constraints_satisfied_rejects_a_violated_constraint() {
+        let mut prover = ProvingAssignment::<blstrs::Scalar>::new();
+        prover.a = vec![blstrs::Scalar::ONE];
+        prover.b = vec![blstrs::Scalar::ONE];
+        prover.c = vec![blstrs::Scalar::ONE + blstrs::Scalar::ONE];
+
+        assert!(matches!(
+            check_constraints_satisfied(&prover),
+            Err(SynthesisError::Unsatisfiable)
+        ));
+    }
+
+    #[test]
+    fn bucket_indices_by_size_groups_and_preserves_order() {
+        let lens = vec![3, 5, 3, 7, 5, 3];
+
+        let groups = bucket_indices_by_size(&lens);
+
+        assert_eq!(
+            groups,
+            vec![(3, vec![0, 2, 5]), (5, vec![1, 4]), (7, vec![3])]
+        );
+    }
+
+    #[test]
+    fn bucket_then_reassemble_restores_input_order() {
+        // Mirrors the group-then-reassemble loop in
+        // `create_proof_batch_priority_inner`: each bucket's "proofs" here
+        // are just the original index itself, so a correct implementation
+        // reassembles back to `0..lens.len()`.
+        let lens = vec![3, 5, 3, 7, 5, 3];
+        let groups = bucket_indices_by_size(&lens);
+
+        let mut reassembled = vec![usize::MAX; lens.len()];
+        for (_, indices) in &groups {
+            let group_results: Vec<usize> = indices.iter().copied().collect();
+            for (result, &original_idx) in group_results.into_iter().zip(indices.iter()) {
+                reassembled[original_idx] = result;
+            }
+        }
+
+        assert_eq!(reassembled, (0..lens.len()).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn write_batch_read_batch_round_trip() {
+        let proofs = vec![sample_proof(), sample_proof(), sample_proof()];
+
+        let mut bytes = Vec::new();
+        write_batch(&proofs, &mut bytes).expect("writing a batch should succeed");
+
+        let read_back: Vec<Proof<Bls12>> =
+            read_batch(&bytes[..]).expect("reading back a just-written batch should succeed");
+
+        assert_eq!(read_back.len(), proofs.len());
+        for (original, read) in proofs.iter().zip(read_back.iter()) {
+            assert_eq!(original.a, read.a);
+            assert_eq!(original.b, read.b);
+            assert_eq!(original.c, read.c);
+        }
+    }
+
+    #[test]
+    fn read_batch_rejects_wrong_magic() {
+        let err = read_batch::<Bls12, _>(&b"NOPE"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn read_batch_rejects_mismatched_version() {
+        let proofs = vec![sample_proof()];
+        let mut bytes = Vec::new();
+        write_batch(&proofs, &mut bytes).unwrap();
+
+        // Corrupt the first byte of the embedded version string, which
+        // should fail the version check rather than being silently
+        // accepted.
+        let version_byte_offset = PROOF_BATCH_MAGIC.len() + 4;
+        bytes[version_byte_offset] = bytes[version_byte_offset].wrapping_add(1);
+
+        let err = read_batch::<Bls12, _>(&bytes[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}